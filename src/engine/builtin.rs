@@ -0,0 +1,111 @@
+use super::eval::evaluate;
+use chess::{Board, ChessMove, MoveGen, Piece};
+use std::time::Instant;
+
+/// Rough material values used only for MVV-LVA move ordering; the leaf
+/// evaluation itself comes from the tapered `evaluate`.
+fn piece_value(piece: Piece) -> i32 {
+    match piece {
+        Piece::Pawn => 100,
+        Piece::Knight => 320,
+        Piece::Bishop => 330,
+        Piece::Rook => 500,
+        Piece::Queen => 900,
+        Piece::King => 20000,
+    }
+}
+
+/// Orders captures first using MVV-LVA (most valuable victim, least valuable
+/// attacker) so alpha-beta prunes earlier branches more effectively.
+fn order_moves(board: &Board, moves: &mut Vec<ChessMove>) {
+    moves.sort_by_key(|m| {
+        let victim = board.piece_on(m.get_dest());
+        match victim {
+            Some(victim) => {
+                let attacker = board.piece_on(m.get_source()).unwrap();
+                -(piece_value(victim) * 10 - piece_value(attacker))
+            }
+            None => i32::MAX,
+        }
+    });
+}
+
+fn negamax(board: &Board, depth: u8, mut alpha: i32, beta: i32) -> i32 {
+    if depth == 0 {
+        return evaluate(board);
+    }
+
+    let mut moves: Vec<ChessMove> = MoveGen::new_legal(board).collect();
+    if moves.is_empty() {
+        return if *board.checkers() != chess::EMPTY {
+            -30000 - depth as i32
+        } else {
+            0
+        };
+    }
+    order_moves(board, &mut moves);
+
+    let mut best = i32::MIN + 1;
+    for chess_move in moves {
+        let next_board = board.make_move_new(chess_move);
+        let score = -negamax(&next_board, depth - 1, -beta, -alpha);
+        if score > best {
+            best = score;
+        }
+        if best > alpha {
+            alpha = best;
+        }
+        if alpha >= beta {
+            break;
+        }
+    }
+    best
+}
+
+/// Iterative-deepening negamax search with alpha-beta pruning. Deepens until
+/// `deadline` is reached (checked between root moves, not mid-branch) or
+/// `max_depth` is hit, and returns the best move found in UCI form (e.g.
+/// `e2e4`, `e7e8q`).
+pub fn search_best_move(board: &Board, deadline: Instant, max_depth: Option<u8>) -> Option<String> {
+    let mut root_moves: Vec<ChessMove> = MoveGen::new_legal(board).collect();
+    if root_moves.is_empty() {
+        return None;
+    }
+    order_moves(board, &mut root_moves);
+
+    let mut best_move = root_moves[0];
+    let mut depth = 1;
+    let depth_limit = max_depth.unwrap_or(32);
+
+    while Instant::now() < deadline && depth <= depth_limit {
+        let mut alpha = i32::MIN + 1;
+        let beta = i32::MAX - 1;
+        let mut best_this_depth = root_moves[0];
+        let mut best_score = i32::MIN + 1;
+
+        for chess_move in &root_moves {
+            if Instant::now() >= deadline {
+                break;
+            }
+            let next_board = board.make_move_new(*chess_move);
+            let score = -negamax(&next_board, depth - 1, -beta, -alpha);
+            if score > best_score {
+                best_score = score;
+                best_this_depth = *chess_move;
+            }
+            if best_score > alpha {
+                alpha = best_score;
+            }
+        }
+
+        best_move = best_this_depth;
+        // Put the best move from this iteration first so the next, deeper
+        // iteration searches it earliest and prunes more effectively.
+        if let Some(pos) = root_moves.iter().position(|m| *m == best_move) {
+            root_moves.swap(0, pos);
+        }
+        depth += 1;
+    }
+
+    Some(best_move.to_string())
+}
@@ -2,45 +2,225 @@ use std::{
     error::Error,
     io::{BufRead, BufReader, Write},
     process::{Child, Command, Stdio},
-    sync::mpsc,
+    str::FromStr,
+    sync::{mpsc, Arc, Mutex},
     thread,
+    time::{Duration, Instant},
 };
 
+use chess::{Board, Color};
+
+mod builtin;
+mod eval;
+
+/// A snapshot of the engine's latest `info` line: search depth, evaluation,
+/// speed, and the principal variation, all in UCI form.
+#[derive(Clone, Debug, Default)]
+pub struct AnalysisInfo {
+    pub depth: u32,
+    pub score_cp: Option<i32>,
+    pub score_mate: Option<i32>,
+    pub nps: Option<u64>,
+    pub pv: Vec<String>,
+}
+
+/// Parses a single UCI `info` line, returning `None` for lines that carry
+/// no depth/score/pv we care about (e.g. `info string ...`).
+pub fn parse_info_line(line: &str) -> Option<AnalysisInfo> {
+    let tokens: Vec<&str> = line.split_whitespace().collect();
+    if tokens.first() != Some(&"info") {
+        return None;
+    }
+
+    let mut info = AnalysisInfo::default();
+    let mut found_anything = false;
+    let mut i = 1;
+    while i < tokens.len() {
+        match tokens[i] {
+            "depth" => {
+                if let Some(d) = tokens.get(i + 1).and_then(|s| s.parse().ok()) {
+                    info.depth = d;
+                    found_anything = true;
+                }
+                i += 2;
+            }
+            "nps" => {
+                if let Some(n) = tokens.get(i + 1).and_then(|s| s.parse().ok()) {
+                    info.nps = Some(n);
+                    found_anything = true;
+                }
+                i += 2;
+            }
+            "score" => {
+                match tokens.get(i + 1) {
+                    Some(&"cp") => {
+                        if let Some(cp) = tokens.get(i + 2).and_then(|s| s.parse().ok()) {
+                            info.score_cp = Some(cp);
+                            found_anything = true;
+                        }
+                    }
+                    Some(&"mate") => {
+                        if let Some(mate) = tokens.get(i + 2).and_then(|s| s.parse().ok()) {
+                            info.score_mate = Some(mate);
+                            found_anything = true;
+                        }
+                    }
+                    _ => {}
+                }
+                i += 3;
+            }
+            "pv" => {
+                info.pv = tokens[i + 1..].iter().map(|s| s.to_string()).collect();
+                found_anything |= !info.pv.is_empty();
+                break;
+            }
+            _ => i += 1,
+        }
+    }
+
+    if found_anything {
+        Some(info)
+    } else {
+        None
+    }
+}
+
+/// Which move-generation backend a `ChessEngine` talks to.
+pub enum EngineKind {
+    /// Spawn an external UCI binary at the given path (e.g. Stockfish).
+    External(String),
+    /// Use the built-in pure-Rust engine; no external process is spawned.
+    Builtin,
+}
+
+/// Opponent strength and resource knobs applied to an external UCI engine
+/// on startup. The built-in engine currently ignores these.
+pub struct EngineConfig {
+    pub skill_level: u8,
+    pub threads: u32,
+    pub hash_mb: u32,
+    /// When set, caps the engine to roughly this Elo via `UCI_LimitStrength`
+    /// / `UCI_Elo` instead of letting it play at full strength.
+    pub uci_elo: Option<u32>,
+}
+
+impl Default for EngineConfig {
+    fn default() -> Self {
+        EngineConfig {
+            skill_level: 10,
+            threads: 4,
+            hash_mb: 128,
+            uci_elo: None,
+        }
+    }
+}
+
+/// How long the engine should think for on a given `get_move` call, mirrored
+/// on the `go` command sent to an external engine.
+pub enum TimeControl {
+    MoveTime(u64),
+    Depth(u8),
+    Clock {
+        wtime: u64,
+        btime: u64,
+        winc: u64,
+        binc: u64,
+    },
+}
+
+impl TimeControl {
+    fn to_go_command(&self) -> String {
+        match self {
+            TimeControl::MoveTime(ms) => format!("go movetime {}\n", ms),
+            TimeControl::Depth(depth) => format!("go depth {}\n", depth),
+            TimeControl::Clock {
+                wtime,
+                btime,
+                winc,
+                binc,
+            } => format!(
+                "go wtime {} btime {} winc {} binc {}\n",
+                wtime, btime, winc, binc
+            ),
+        }
+    }
+
+    /// Translates the time control into a deadline and, for `Depth`, a hard
+    /// ply limit for the built-in search. `side_to_move` only matters for
+    /// `Clock`, where each side's own remaining time applies.
+    fn builtin_budget(&self, side_to_move: Color) -> (Instant, Option<u8>) {
+        match self {
+            TimeControl::MoveTime(ms) => (Instant::now() + Duration::from_millis(*ms), None),
+            TimeControl::Depth(depth) => (Instant::now() + Duration::from_secs(30), Some(*depth)),
+            TimeControl::Clock { wtime, btime, .. } => {
+                let remaining = if side_to_move == Color::White {
+                    *wtime
+                } else {
+                    *btime
+                };
+                // Simple fixed fraction of the remaining clock; no proper
+                // time-management curve yet.
+                let budget_ms = (remaining / 20).max(50);
+                (Instant::now() + Duration::from_millis(budget_ms), None)
+            }
+        }
+    }
+}
+
 pub struct ChessEngine {
+    kind: EngineKind,
     process: Option<Child>,
     move_receiver: mpsc::Receiver<String>,
     move_sender: mpsc::Sender<String>,
+    analysis: Arc<Mutex<AnalysisInfo>>,
 }
 
 impl ChessEngine {
-    pub fn new() -> Self {
+    pub fn new(kind: EngineKind) -> Self {
         let (tx, rx) = mpsc::channel();
-        
+
         ChessEngine {
+            kind,
             process: None,
             move_receiver: rx,
             move_sender: tx,
+            analysis: Arc::new(Mutex::new(AnalysisInfo::default())),
         }
     }
-    
-    pub fn start(&mut self, engine_path: &str) -> Result<(), Box<dyn Error>> {
+
+    /// The most recent depth/eval/PV reported by the engine's `info` lines.
+    pub fn analysis(&self) -> AnalysisInfo {
+        self.analysis.lock().unwrap().clone()
+    }
+
+    pub fn start(&mut self, config: &EngineConfig) -> Result<(), Box<dyn Error>> {
+        let engine_path = match &self.kind {
+            EngineKind::External(path) => path.clone(),
+            EngineKind::Builtin => return Ok(()),
+        };
+
         let process = Command::new(engine_path)
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
             .spawn()?;
 
         self.process = Some(process);
-        
+
         // Initialize UCI engine
         if let Some(ref mut process) = self.process {
             let mut stdin = process.stdin.take().unwrap();
             stdin.write_all(b"uci\n")?;
             stdin.write_all(b"isready\n")?;
-            stdin.write_all(b"setoption name Skill Level value 10\n")?; // Set skill level (1-20)
-            stdin.write_all(b"setoption name Threads value 4\n")?; // Use 4 threads
-            stdin.write_all(b"setoption name Hash value 128\n")?; // Use 128MB hash
+            stdin.write_all(format!("setoption name Skill Level value {}\n", config.skill_level).as_bytes())?;
+            stdin.write_all(format!("setoption name Threads value {}\n", config.threads).as_bytes())?;
+            stdin.write_all(format!("setoption name Hash value {}\n", config.hash_mb).as_bytes())?;
             stdin.write_all(b"setoption name UCI_AnalyseMode value false\n")?;
-            stdin.write_all(b"setoption name UCI_LimitStrength value false\n")?;
+            if let Some(elo) = config.uci_elo {
+                stdin.write_all(b"setoption name UCI_LimitStrength value true\n")?;
+                stdin.write_all(format!("setoption name UCI_Elo value {}\n", elo).as_bytes())?;
+            } else {
+                stdin.write_all(b"setoption name UCI_LimitStrength value false\n")?;
+            }
             stdin.flush()?;
             
             // Read engine output in a separate thread
@@ -49,7 +229,8 @@ impl ChessEngine {
             
             // Get a clone of the sender to pass to the thread
             let tx_clone = self.move_sender.clone();
-            
+            let analysis_clone = self.analysis.clone();
+
             thread::spawn(move || {
                 for line in reader.lines() {
                     if let Ok(line) = line {
@@ -58,6 +239,8 @@ impl ChessEngine {
                             if parts.len() >= 2 {
                                 tx_clone.send(parts[1].to_string()).unwrap_or(());
                             }
+                        } else if let Some(info) = parse_info_line(&line) {
+                            *analysis_clone.lock().unwrap() = info;
                         }
                     }
                 }
@@ -70,19 +253,34 @@ impl ChessEngine {
         Ok(())
     }
     
-    pub fn get_move(&mut self, fen: &str) -> Result<(), Box<dyn Error>> {
-        if let Some(ref mut process) = self.process {
-            if let Some(stdin) = process.stdin.as_mut() {
-                // Send position to engine
-                let position_cmd = format!("position fen {}\n", fen);
-                stdin.write_all(position_cmd.as_bytes())?;
-                
-                // Ask engine to think
-                stdin.write_all(b"go movetime 2000\n")?;
-                stdin.flush()?;
+    pub fn get_move(&mut self, fen: &str, time_control: &TimeControl) -> Result<(), Box<dyn Error>> {
+        match &self.kind {
+            EngineKind::External(_) => {
+                if let Some(ref mut process) = self.process {
+                    if let Some(stdin) = process.stdin.as_mut() {
+                        // Send position to engine
+                        let position_cmd = format!("position fen {}\n", fen);
+                        stdin.write_all(position_cmd.as_bytes())?;
+
+                        // Ask engine to think
+                        stdin.write_all(time_control.to_go_command().as_bytes())?;
+                        stdin.flush()?;
+                    }
+                }
+            }
+            EngineKind::Builtin => {
+                let board = Board::from_str(fen).map_err(|e| format!("invalid FEN: {:?}", e))?;
+                let (deadline, max_depth) = time_control.builtin_budget(board.side_to_move());
+                let tx_clone = self.move_sender.clone();
+
+                thread::spawn(move || {
+                    if let Some(best_move) = builtin::search_best_move(&board, deadline, max_depth) {
+                        tx_clone.send(best_move).unwrap_or(());
+                    }
+                });
             }
         }
-        
+
         Ok(())
     }
     
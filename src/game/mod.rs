@@ -1,4 +1,42 @@
-use chess::{ChessMove, Color, Game, MoveGen, Piece, Square, Rank, File};
+use chess::{Board, ChessMove, Color, Game, GameResult, MoveGen, Piece, Square, Rank, File};
+use std::error::Error;
+use std::str::FromStr;
+use std::time::Duration;
+
+pub mod pgn;
+
+/// Resolves a UCI move string (e.g. `e2e4`, `e7e8q`) against the legal
+/// moves of `board`.
+pub fn uci_to_move(board: &Board, uci: &str) -> Option<ChessMove> {
+    if uci.len() < 4 {
+        return None;
+    }
+
+    let bytes = uci.as_bytes();
+    let from_file = (bytes[0].wrapping_sub(b'a')) as usize;
+    let from_rank = (bytes[1].wrapping_sub(b'1')) as usize;
+    let to_file = (bytes[2].wrapping_sub(b'a')) as usize;
+    let to_rank = (bytes[3].wrapping_sub(b'1')) as usize;
+    if from_file >= 8 || from_rank >= 8 || to_file >= 8 || to_rank >= 8 {
+        return None;
+    }
+
+    let from_square = Square::make_square(Rank::from_index(from_rank), File::from_index(from_file));
+    let to_square = Square::make_square(Rank::from_index(to_rank), File::from_index(to_file));
+    let promotion = uci.chars().nth(4).and_then(|c| match c {
+        'q' => Some(Piece::Queen),
+        'r' => Some(Piece::Rook),
+        'b' => Some(Piece::Bishop),
+        'n' => Some(Piece::Knight),
+        _ => None,
+    });
+
+    MoveGen::new_legal(board).find(|m| {
+        m.get_source() == from_square
+            && m.get_dest() == to_square
+            && (promotion.is_none() || m.get_promotion() == promotion)
+    })
+}
 
 pub struct ChessGame {
     game: Game,
@@ -6,6 +44,14 @@ pub struct ChessGame {
     possible_moves: Vec<ChessMove>,
     message: String,
     thinking: bool,
+    white_time_left: Duration,
+    black_time_left: Duration,
+    white_increment: Duration,
+    black_increment: Duration,
+    /// Board position before each move, paired with the move itself, so
+    /// `undo_move` can restore it without replaying from the start.
+    history: Vec<(Board, ChessMove)>,
+    redo_stack: Vec<(Board, ChessMove)>,
 }
 
 impl ChessGame {
@@ -16,8 +62,41 @@ impl ChessGame {
             possible_moves: Vec::new(),
             message: String::from("Welcome to Chess Terminal! You play as White."),
             thinking: false,
+            white_time_left: Duration::from_secs(0),
+            black_time_left: Duration::from_secs(0),
+            white_increment: Duration::from_secs(0),
+            black_increment: Duration::from_secs(0),
+            history: Vec::new(),
+            redo_stack: Vec::new(),
         }
     }
+
+    /// Sets up a real time control: both sides start with `initial` time and
+    /// gain `increment` after each of their moves.
+    pub fn set_time_control(&mut self, initial: Duration, increment: Duration) {
+        self.white_time_left = initial;
+        self.black_time_left = initial;
+        self.white_increment = increment;
+        self.black_increment = increment;
+    }
+
+    pub fn time_left(&self, color: Color) -> Duration {
+        match color {
+            Color::White => self.white_time_left,
+            Color::Black => self.black_time_left,
+        }
+    }
+
+    /// Decrements the side-to-move's clock by `elapsed`, returning `true` if
+    /// that side has just run out of time.
+    pub fn tick_clock(&mut self, elapsed: Duration) -> bool {
+        let time_left = match self.game.side_to_move() {
+            Color::White => &mut self.white_time_left,
+            Color::Black => &mut self.black_time_left,
+        };
+        *time_left = time_left.saturating_sub(elapsed);
+        time_left.is_zero()
+    }
     
     pub fn current_position(&self) -> chess::Board {
         self.game.current_position()
@@ -62,7 +141,15 @@ impl ChessGame {
             let possible_move = self.possible_moves.iter().find(|m| m.get_dest() == square);
             
             if let Some(chess_move) = possible_move {
-                if self.game.make_move(*chess_move) {
+                let chess_move = *chess_move;
+                let board_before = self.game.current_position();
+                if self.game.make_move(chess_move) {
+                    self.history.push((board_before, chess_move));
+                    self.redo_stack.clear();
+                    match !self.game.side_to_move() {
+                        Color::White => self.white_time_left += self.white_increment,
+                        Color::Black => self.black_time_left += self.black_increment,
+                    }
                     self.message = format!("Move: {}", chess_move);
                     self.selected_square = None;
                     self.possible_moves.clear();
@@ -155,6 +242,12 @@ impl ChessGame {
                 
                 if promotion.is_none() || m.get_promotion() == promotion {
                     if self.game.make_move(m) {
+                        self.history.push((board, m));
+                        self.redo_stack.clear();
+                        match !self.game.side_to_move() {
+                            Color::White => self.white_time_left += self.white_increment,
+                            Color::Black => self.black_time_left += self.black_increment,
+                        }
                         self.message = format!("Engine moved: {}", uci_move);
                         self.thinking = false;
                         return true;
@@ -169,4 +262,142 @@ impl ChessGame {
     pub fn game_result(&self) -> Option<chess::GameResult> {
         self.game.result()
     }
+
+    /// Takes back the last move. Against the engine this pops both the
+    /// engine's reply and the player's move, so control returns to the
+    /// human; with only one move recorded it just pops that one.
+    pub fn undo_move(&mut self) -> bool {
+        let Some(last) = self.history.pop() else {
+            return false;
+        };
+        let (board_before, _) = last;
+        self.redo_stack.push(last);
+        self.game = Game::new_with_board(board_before);
+
+        if let Some(prior) = self.history.pop() {
+            let (board_before, _) = prior;
+            self.redo_stack.push(prior);
+            self.game = Game::new_with_board(board_before);
+        }
+
+        self.selected_square = None;
+        self.possible_moves.clear();
+        self.message = "Move undone".to_string();
+        true
+    }
+
+    /// Re-applies the most recently undone move, if any.
+    pub fn redo_move(&mut self) -> bool {
+        let Some((_, chess_move)) = self.redo_stack.pop() else {
+            return false;
+        };
+        let board_before = self.game.current_position();
+        if self.game.make_move(chess_move) {
+            self.history.push((board_before, chess_move));
+            self.selected_square = None;
+            self.possible_moves.clear();
+            self.message = format!("Redid: {}", chess_move);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Current position as a FEN string.
+    pub fn to_fen(&self) -> String {
+        self.game.current_position().to_string()
+    }
+
+    /// Rebuilds a `ChessGame` starting from an arbitrary FEN position. Move
+    /// history starts empty since there is no game to replay.
+    pub fn from_fen(fen: &str) -> Result<Self, Box<dyn Error>> {
+        let board = Board::from_str(fen).map_err(|e| format!("invalid FEN: {:?}", e))?;
+        let mut game = ChessGame::new();
+        game.game = Game::new_with_board(board);
+        Ok(game)
+    }
+
+    /// Converts a UCI principal variation (as reported by the engine) into
+    /// space-separated SAN, stopping early at the first move that fails to
+    /// resolve against the position it's played from.
+    pub fn pv_to_san(&self, pv: &[String]) -> String {
+        let mut board = self.game.current_position();
+        let mut san_moves = Vec::new();
+
+        for uci_move in pv {
+            let Some(chess_move) = uci_to_move(&board, uci_move) else {
+                break;
+            };
+            san_moves.push(pgn::move_to_san(&board, chess_move));
+            board = board.make_move_new(chess_move);
+        }
+
+        san_moves.join(" ")
+    }
+
+    /// Exports the game so far as a standards-compliant PGN with the Seven
+    /// Tag Roster and the SAN move list derived from the recorded history.
+    pub fn to_pgn(&self) -> String {
+        let result = match self.game.result() {
+            Some(GameResult::WhiteCheckmates) | Some(GameResult::BlackResigns) => "1-0",
+            Some(GameResult::BlackCheckmates) | Some(GameResult::WhiteResigns) => "0-1",
+            Some(GameResult::Stalemate)
+            | Some(GameResult::DrawAccepted)
+            | Some(GameResult::DrawDeclared) => "1/2-1/2",
+            None => "*",
+        };
+
+        let mut pgn = String::new();
+        pgn.push_str("[Event \"Casual Game\"]\n");
+        pgn.push_str("[Site \"Chess Terminal\"]\n");
+        pgn.push_str("[Date \"????.??.??\"]\n");
+        pgn.push_str("[Round \"1\"]\n");
+        pgn.push_str("[White \"Player\"]\n");
+        pgn.push_str("[Black \"Engine\"]\n");
+        pgn.push_str(&format!("[Result \"{}\"]\n\n", result));
+
+        let mut movetext = String::new();
+        for (i, (board, chess_move)) in self.history.iter().enumerate() {
+            if i % 2 == 0 {
+                movetext.push_str(&format!("{}. ", i / 2 + 1));
+            }
+            movetext.push_str(&pgn::move_to_san(board, *chess_move));
+            movetext.push(' ');
+        }
+        movetext.push_str(result);
+
+        pgn.push_str(movetext.trim_start());
+        pgn.push('\n');
+        pgn
+    }
+
+    /// Rebuilds a `ChessGame` by replaying the SAN move text of a PGN file,
+    /// resolving each move against the legal moves generated at that ply.
+    pub fn from_pgn(contents: &str) -> Result<Self, Box<dyn Error>> {
+        let mut game = ChessGame::new();
+
+        for line in contents.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('[') {
+                continue;
+            }
+
+            for token in line.split_whitespace() {
+                if token.ends_with('.') || matches!(token, "1-0" | "0-1" | "1/2-1/2" | "*") {
+                    continue;
+                }
+
+                let board = game.game.current_position();
+                let chess_move = pgn::san_to_move(&board, token)
+                    .ok_or_else(|| format!("unresolvable move in PGN: {}", token))?;
+
+                if !game.game.make_move(chess_move) {
+                    return Err(format!("illegal move in PGN: {}", token).into());
+                }
+                game.history.push((board, chess_move));
+            }
+        }
+
+        Ok(game)
+    }
 }
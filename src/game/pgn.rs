@@ -0,0 +1,132 @@
+use chess::{Board, ChessMove, File, MoveGen, Piece, Rank, Square};
+
+fn file_char(file: File) -> char {
+    (b'a' + file.to_index() as u8) as char
+}
+
+fn rank_char(rank: Rank) -> char {
+    (b'1' + rank.to_index() as u8) as char
+}
+
+fn square_str(square: Square) -> String {
+    format!("{}{}", file_char(square.get_file()), rank_char(square.get_rank()))
+}
+
+fn piece_letter(piece: Piece) -> char {
+    match piece {
+        Piece::Knight => 'N',
+        Piece::Bishop => 'B',
+        Piece::Rook => 'R',
+        Piece::Queen => 'Q',
+        Piece::King => 'K',
+        Piece::Pawn => unreachable!("pawns are never prefixed with a piece letter"),
+    }
+}
+
+/// Suffixes a base move string (e.g. `O-O`) with `+`/`#` if the position
+/// after `mv` leaves the opponent in check or checkmate.
+fn with_check_suffix(board: &Board, mv: ChessMove, base: String) -> String {
+    let next = board.make_move_new(mv);
+    let mut san = base;
+    if *next.checkers() != chess::EMPTY {
+        if next.status() == chess::BoardStatus::Checkmate {
+            san.push('#');
+        } else {
+            san.push('+');
+        }
+    }
+    san
+}
+
+/// Disambiguates `mv` against other legal moves of the same piece type
+/// landing on the same square, e.g. `Nbd7` or `R1e2`.
+fn disambiguation(board: &Board, mv: ChessMove) -> String {
+    let piece = board.piece_on(mv.get_source()).unwrap();
+    let color = board.color_on(mv.get_source()).unwrap();
+
+    let mut same_file = false;
+    let mut same_rank = false;
+    let mut ambiguous = false;
+
+    for other in MoveGen::new_legal(board) {
+        if other == mv || other.get_dest() != mv.get_dest() {
+            continue;
+        }
+        if board.piece_on(other.get_source()) != Some(piece)
+            || board.color_on(other.get_source()) != Some(color)
+        {
+            continue;
+        }
+        ambiguous = true;
+        if other.get_source().get_file() == mv.get_source().get_file() {
+            same_file = true;
+        }
+        if other.get_source().get_rank() == mv.get_source().get_rank() {
+            same_rank = true;
+        }
+    }
+
+    if !ambiguous {
+        String::new()
+    } else if !same_file {
+        file_char(mv.get_source().get_file()).to_string()
+    } else if !same_rank {
+        rank_char(mv.get_source().get_rank()).to_string()
+    } else {
+        square_str(mv.get_source())
+    }
+}
+
+/// Converts a legal move into Standard Algebraic Notation for the position
+/// it is played from, including disambiguation, captures, promotion, and
+/// check/checkmate suffixes.
+pub fn move_to_san(board: &Board, mv: ChessMove) -> String {
+    let piece = board.piece_on(mv.get_source()).unwrap();
+
+    if piece == Piece::King {
+        let file_diff = mv.get_dest().get_file().to_index() as i32
+            - mv.get_source().get_file().to_index() as i32;
+        if file_diff == 2 {
+            return with_check_suffix(board, mv, "O-O".to_string());
+        }
+        if file_diff == -2 {
+            return with_check_suffix(board, mv, "O-O-O".to_string());
+        }
+    }
+
+    let dest_occupied = board.piece_on(mv.get_dest()).is_some();
+    let is_en_passant = piece == Piece::Pawn
+        && mv.get_dest().get_file() != mv.get_source().get_file()
+        && !dest_occupied;
+    let is_capture = dest_occupied || is_en_passant;
+
+    let mut san = String::new();
+    if piece == Piece::Pawn {
+        if is_capture {
+            san.push(file_char(mv.get_source().get_file()));
+        }
+    } else {
+        san.push(piece_letter(piece));
+        san.push_str(&disambiguation(board, mv));
+    }
+
+    if is_capture {
+        san.push('x');
+    }
+    san.push_str(&square_str(mv.get_dest()));
+
+    if let Some(promotion) = mv.get_promotion() {
+        san.push('=');
+        san.push(piece_letter(promotion));
+    }
+
+    with_check_suffix(board, mv, san)
+}
+
+/// Finds the legal move from `board` whose SAN matches `token`, ignoring a
+/// trailing `+`/`#` so PGN files that omit or vary check annotations still
+/// resolve.
+pub fn san_to_move(board: &Board, token: &str) -> Option<ChessMove> {
+    let token = token.trim_end_matches(['+', '#']);
+    MoveGen::new_legal(board).find(|mv| move_to_san(board, *mv).trim_end_matches(['+', '#']) == token)
+}
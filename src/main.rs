@@ -1,13 +1,17 @@
 use std::{
     error::Error,
+    fs,
     io,
     process::{Command, Stdio},
-    sync::mpsc,
+    str::FromStr,
+    sync::{mpsc, Arc, Mutex},
     thread,
     time::{Duration, Instant},
 };
 
-use chess::{Board, ChessMove, Color, Game, MoveGen, Piece, Square, Rank, File};
+use chess::{Board, ChessMove, Color, Game, GameResult, MoveGen, Piece, Square, Rank, File};
+use chess_terminal::engine::{parse_info_line, AnalysisInfo};
+use chess_terminal::game::{pgn::{move_to_san, san_to_move}, uci_to_move};
 use crossterm::{
     event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode},
     execute,
@@ -23,36 +27,504 @@ use ratatui::{
 };
 use std::io::{BufRead, BufReader, Write};
 
+fn format_clock(time_left: Duration) -> String {
+    let total_secs = time_left.as_secs();
+    format!("{:02}:{:02}", total_secs / 60, total_secs % 60)
+}
+
+/// The time control a fresh game (or a loaded FEN/PGN) starts with: 5
+/// minutes per side plus a 3 second increment per move.
+const DEFAULT_TIME: Duration = Duration::from_secs(5 * 60);
+const DEFAULT_INCREMENT: Duration = Duration::from_secs(3);
+
+/// Strips PGN markup that isn't part of the move list itself: `{...}`
+/// comments, `;...` end-of-line comments, and (possibly nested) `(...)`
+/// recursive variations.
+fn strip_pgn_markup(movetext: &str) -> String {
+    let mut cleaned = String::new();
+    let mut variation_depth = 0u32;
+    let mut chars = movetext.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' => {
+                for c in chars.by_ref() {
+                    if c == '}' {
+                        break;
+                    }
+                }
+            }
+            ';' => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            }
+            '(' => variation_depth += 1,
+            ')' => variation_depth = variation_depth.saturating_sub(1),
+            _ if variation_depth > 0 => {}
+            _ => cleaned.push(c),
+        }
+    }
+
+    cleaned
+}
+
+/// Strips a leading move number (e.g. `1.` or `12...`) glued to a SAN
+/// token with no separating space, leaving bare tokens like `1-0`
+/// untouched.
+fn strip_move_number(token: &str) -> &str {
+    let after_digits = token.trim_start_matches(|c: char| c.is_ascii_digit());
+    if after_digits.len() == token.len() || !after_digits.starts_with('.') {
+        token
+    } else {
+        after_digits.trim_start_matches('.')
+    }
+}
+
+const EVAL_BAR_WIDTH: i32 = 20;
+
+/// Translates a UCI principal variation into SAN, stopping at the first
+/// move that no longer resolves (e.g. a truncated `pv` line).
+fn pv_to_san(board: &Board, pv: &[String]) -> String {
+    let mut current = *board;
+    let mut moves = Vec::new();
+    for uci in pv {
+        let Some(chess_move) = uci_to_move(&current, uci) else {
+            break;
+        };
+        moves.push(move_to_san(&current, chess_move));
+        current = current.make_move_new(chess_move);
+    }
+    moves.join(" ")
+}
+
+/// Renders the eval bar as a fixed-width ASCII gauge, e.g. `[############--------]`.
+/// Mate scores peg the bar fully to whichever side is winning. UCI scores are
+/// relative to the side to move, so `perspective` flips them to White's view.
+fn eval_bar(analysis: &AnalysisInfo, perspective: Color) -> String {
+    let sign = if perspective == Color::Black { -1 } else { 1 };
+
+    let filled = if let Some(mate) = analysis.score_mate {
+        if mate * sign > 0 { EVAL_BAR_WIDTH } else { 0 }
+    } else {
+        let cp = (analysis.score_cp.unwrap_or(0) * sign).clamp(-1000, 1000);
+        (cp + 1000) * EVAL_BAR_WIDTH / 2000
+    }
+    .clamp(0, EVAL_BAR_WIDTH) as usize;
+
+    format!(
+        "[{}{}]",
+        "#".repeat(filled),
+        "-".repeat(EVAL_BAR_WIDTH as usize - filled)
+    )
+}
+
+/// Same White-relative normalization as `eval_bar`; see its doc comment.
+fn eval_label(analysis: &AnalysisInfo, perspective: Color) -> String {
+    let sign = if perspective == Color::Black { -1 } else { 1 };
+
+    if let Some(mate) = analysis.score_mate {
+        format!("#{}", mate * sign)
+    } else if let Some(cp) = analysis.score_cp {
+        format!("{:+.2}", (cp * sign) as f32 / 100.0)
+    } else {
+        "--".to_string()
+    }
+}
+
+/// The kind of value a UCI `option` line declares, per the protocol spec.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum UciOptionKind {
+    Check,
+    Spin,
+    Combo,
+    String,
+    Button,
+}
+
+/// An option the connected engine advertised via `uci`, e.g. `Skill Level`
+/// or `UCI_Elo`, with enough of its declaration to validate and adjust it.
+#[derive(Clone, Debug)]
+struct UciOption {
+    name: String,
+    kind: UciOptionKind,
+    default: String,
+    min: Option<i64>,
+    max: Option<i64>,
+    vars: Vec<String>,
+}
+
+/// Settings we prefer to apply at startup, keyed by the option name the
+/// engine must advertise before we'll touch it.
+const DEFAULT_ENGINE_SETTINGS: &[(&str, &str)] = &[
+    ("Skill Level", "10"),
+    ("Threads", "4"),
+    ("Hash", "128"),
+    ("UCI_LimitStrength", "false"),
+];
+
+/// Parses a single `option name ... type ... [default ...] [min ...] [max
+/// ...] [var ...]*` line from the engine's `uci` response. Returns `None`
+/// for any other line (e.g. `id name ...`, `uciok`).
+fn parse_uci_option_line(line: &str) -> Option<UciOption> {
+    let tokens: Vec<&str> = line.split_whitespace().collect();
+    if tokens.first() != Some(&"option") {
+        return None;
+    }
+
+    let name_start = tokens.iter().position(|&t| t == "name")? + 1;
+    let type_pos = tokens.iter().position(|&t| t == "type")?;
+    if type_pos <= name_start {
+        return None;
+    }
+    let name = tokens[name_start..type_pos].join(" ");
+
+    let kind = match tokens.get(type_pos + 1) {
+        Some(&"check") => UciOptionKind::Check,
+        Some(&"spin") => UciOptionKind::Spin,
+        Some(&"combo") => UciOptionKind::Combo,
+        Some(&"button") => UciOptionKind::Button,
+        _ => UciOptionKind::String,
+    };
+
+    let mut default = String::new();
+    let mut min = None;
+    let mut max = None;
+    let mut vars = Vec::new();
+
+    let mut i = type_pos + 2;
+    while i < tokens.len() {
+        match tokens[i] {
+            "default" => {
+                let mut end = i + 1;
+                while end < tokens.len() && !matches!(tokens[end], "min" | "max" | "var") {
+                    end += 1;
+                }
+                default = tokens[i + 1..end].join(" ");
+                i = end;
+            }
+            "min" => {
+                min = tokens.get(i + 1).and_then(|s| s.parse().ok());
+                i += 2;
+            }
+            "max" => {
+                max = tokens.get(i + 1).and_then(|s| s.parse().ok());
+                i += 2;
+            }
+            "var" => {
+                let mut end = i + 1;
+                while end < tokens.len() && tokens[end] != "var" {
+                    end += 1;
+                }
+                vars.push(tokens[i + 1..end].join(" "));
+                i = end;
+            }
+            _ => i += 1,
+        }
+    }
+
+    Some(UciOption {
+        name,
+        kind,
+        default,
+        min,
+        max,
+        vars,
+    })
+}
+
+/// What the single-line input overlay is currently collecting.
+enum InputMode {
+    Fen,
+    PgnPath,
+}
+
 // App state
 struct App {
     game: Game,
     selected_square: Option<Square>,
     possible_moves: Vec<ChessMove>,
     engine_process: Option<std::process::Child>,
-    engine_move: Option<ChessMove>,
-    player_color: Color,
     message: String,
     thinking: bool,
-    engine_move_receiver: Option<mpsc::Receiver<String>>,
-    engine_move_sender: Option<mpsc::Sender<String>>,
+    /// Each received item is `(bestmove, ponder_move)` in UCI form.
+    engine_move_receiver: Option<mpsc::Receiver<(String, Option<String>)>>,
+    engine_move_sender: Option<mpsc::Sender<(String, Option<String>)>>,
+    /// Board position before each move, paired with the move itself, used to
+    /// derive SAN when exporting the game as PGN.
+    move_history: Vec<(Board, ChessMove)>,
+    white_time_left: Duration,
+    black_time_left: Duration,
+    white_increment: Duration,
+    black_increment: Duration,
+    /// Set once a side's clock hits zero; further moves are rejected.
+    time_forfeit: Option<Color>,
+    /// Latest depth/eval/PV reported by the engine's `info` lines, updated
+    /// from the reader thread in `start_engine`.
+    analysis: Arc<Mutex<AnalysisInfo>>,
+    /// Options the connected engine advertised during the `uci` handshake.
+    engine_options: Vec<UciOption>,
+    /// Current value for each entry in `engine_options`, same order.
+    engine_settings: Vec<String>,
+    settings_open: bool,
+    settings_selected: usize,
+    /// Index into `move_history` currently shown on screen; `None` means
+    /// the live position (equivalent to `move_history.len()`). Set by
+    /// `Left`/`Right` to browse past positions without disturbing the game.
+    history_cursor: Option<usize>,
+    /// `true` while the engine is searching the predicted position ahead of
+    /// the human's move (a UCI `go ponder`).
+    pondering: bool,
+    /// The move the engine predicted the human would play, from the most
+    /// recent `bestmove ... ponder <p>`.
+    ponder_move: Option<ChessMove>,
+    /// Set when a ponder miss sends `stop`: the engine will still emit one
+    /// `bestmove` for the abandoned ponder search (on the predicted, not the
+    /// actual, position), which must be dropped rather than applied.
+    discard_next_bestmove: bool,
+    /// `Some` while a FEN or PGN path text input overlay is collecting
+    /// keystrokes; see `submit_input`.
+    input_mode: Option<InputMode>,
+    input_buffer: String,
 }
 
 impl App {
     fn new() -> Self {
         // Create a channel for engine moves
         let (tx, rx) = mpsc::channel();
-        
+
         App {
             game: Game::new(),
             selected_square: None,
             possible_moves: Vec::new(),
             engine_process: None,
-            engine_move: None,
-            player_color: Color::White,
             message: String::from("Welcome to Chess Terminal! You play as White."),
             thinking: false,
             engine_move_receiver: Some(rx),
             engine_move_sender: Some(tx),
+            move_history: Vec::new(),
+            white_time_left: DEFAULT_TIME,
+            black_time_left: DEFAULT_TIME,
+            white_increment: DEFAULT_INCREMENT,
+            black_increment: DEFAULT_INCREMENT,
+            time_forfeit: None,
+            analysis: Arc::new(Mutex::new(AnalysisInfo::default())),
+            engine_options: Vec::new(),
+            engine_settings: Vec::new(),
+            settings_open: false,
+            settings_selected: 0,
+            history_cursor: None,
+            pondering: false,
+            ponder_move: None,
+            discard_next_bestmove: false,
+            input_mode: None,
+            input_buffer: String::new(),
+        }
+    }
+
+    /// The board as of `index` plies into `move_history` (`index ==
+    /// move_history.len()` is the live position).
+    fn board_at(&self, index: usize) -> Board {
+        if index < self.move_history.len() {
+            self.move_history[index].0
+        } else {
+            self.game.current_position()
+        }
+    }
+
+    fn display_index(&self) -> usize {
+        self.history_cursor.unwrap_or(self.move_history.len())
+    }
+
+    /// The position currently shown on screen, which may be a past ply
+    /// while browsing history.
+    fn display_board(&self) -> Board {
+        self.board_at(self.display_index())
+    }
+
+    /// Steps the displayed position one ply back into history.
+    fn browse_back(&mut self) {
+        let idx = self.display_index();
+        if idx > 0 {
+            self.history_cursor = Some(idx - 1);
+            self.selected_square = None;
+            self.possible_moves.clear();
+        }
+    }
+
+    /// Steps the displayed position one ply forward, returning to live play
+    /// once the cursor reaches the end of history.
+    fn browse_forward(&mut self) {
+        let idx = self.display_index();
+        if idx >= self.move_history.len() {
+            return;
+        }
+        let next = idx + 1;
+        self.history_cursor = if next >= self.move_history.len() {
+            None
+        } else {
+            Some(next)
+        };
+        self.selected_square = None;
+        self.possible_moves.clear();
+    }
+
+    fn time_left(&self, color: Color) -> Duration {
+        match color {
+            Color::White => self.white_time_left,
+            Color::Black => self.black_time_left,
+        }
+    }
+
+    /// Decrements the side-to-move's clock by `elapsed`, flagging a timeout
+    /// loss the first time it reaches zero.
+    fn tick_clock(&mut self, elapsed: Duration) {
+        if self.time_forfeit.is_some() || self.game.result().is_some() {
+            return;
+        }
+
+        let side_to_move = self.game.side_to_move();
+        let time_left = match side_to_move {
+            Color::White => &mut self.white_time_left,
+            Color::Black => &mut self.black_time_left,
+        };
+        *time_left = time_left.saturating_sub(elapsed);
+
+        if time_left.is_zero() {
+            self.time_forfeit = Some(side_to_move);
+            self.message = format!(
+                "{:?} ran out of time!",
+                side_to_move
+            );
+        }
+    }
+
+    /// Exports the game so far as a standards-compliant PGN with the Seven
+    /// Tag Roster and the SAN move list derived from `move_history`.
+    fn to_pgn(&self) -> String {
+        let result = match self.game.result() {
+            Some(GameResult::WhiteCheckmates) | Some(GameResult::BlackResigns) => "1-0",
+            Some(GameResult::BlackCheckmates) | Some(GameResult::WhiteResigns) => "0-1",
+            Some(GameResult::Stalemate)
+            | Some(GameResult::DrawAccepted)
+            | Some(GameResult::DrawDeclared) => "1/2-1/2",
+            None => "*",
+        };
+
+        let mut pgn = String::new();
+        pgn.push_str("[Event \"Casual Game\"]\n");
+        pgn.push_str("[Site \"Chess Terminal\"]\n");
+        pgn.push_str("[Date \"????.??.??\"]\n");
+        pgn.push_str("[Round \"1\"]\n");
+        pgn.push_str("[White \"Player\"]\n");
+        pgn.push_str("[Black \"Engine\"]\n");
+        pgn.push_str(&format!("[Result \"{}\"]\n\n", result));
+
+        let mut movetext = String::new();
+        for (i, (board, chess_move)) in self.move_history.iter().enumerate() {
+            if i % 2 == 0 {
+                movetext.push_str(&format!("{}. ", i / 2 + 1));
+            }
+            movetext.push_str(&move_to_san(board, *chess_move));
+            movetext.push(' ');
+        }
+        movetext.push_str(result);
+
+        pgn.push_str(movetext.trim_start());
+        pgn.push('\n');
+        pgn
+    }
+
+    /// Validates `fen` and, if legal, switches the live game to that
+    /// position for puzzle/analysis work. Move history starts empty since
+    /// there is no game to replay. Clocks and any standing time forfeit are
+    /// reset, since a freshly loaded position isn't the one that ran out.
+    fn load_fen(&mut self, fen: &str) -> Result<(), Box<dyn Error>> {
+        let board = Board::from_str(fen).map_err(|e| format!("invalid FEN: {:?}", e))?;
+        self.game = Game::new_with_board(board);
+        self.move_history.clear();
+        self.selected_square = None;
+        self.possible_moves.clear();
+        self.history_cursor = None;
+        self.pondering = false;
+        self.ponder_move = None;
+        self.time_forfeit = None;
+        self.white_time_left = DEFAULT_TIME;
+        self.black_time_left = DEFAULT_TIME;
+        Ok(())
+    }
+
+    /// Loads a PGN file, resolving its SAN move text against the legal
+    /// moves generated at each ply, and replays it into the history stack
+    /// so the user can step through the imported game. Comments (`{...}`),
+    /// recursive variations (`(...)`), and NAGs (`$n`) are discarded rather
+    /// than fed to the move resolver, and a move number glued to its move
+    /// (`1.e4`) is split off before resolving. Clocks and any standing time
+    /// forfeit are reset, since a freshly loaded game isn't the one that
+    /// ran out.
+    fn load_pgn(&mut self, path: &str) -> Result<(), Box<dyn Error>> {
+        let contents = fs::read_to_string(path)?;
+        let movetext: String = contents
+            .lines()
+            .filter(|line| !line.trim().starts_with('['))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let movetext = strip_pgn_markup(&movetext);
+
+        let mut game = Game::new();
+        let mut history = Vec::new();
+
+        for raw_token in movetext.split_whitespace() {
+            if raw_token.starts_with('$') || matches!(raw_token, "1-0" | "0-1" | "1/2-1/2" | "*") {
+                continue;
+            }
+            let token = strip_move_number(raw_token);
+            if token.is_empty() {
+                continue;
+            }
+
+            let board = game.current_position();
+            let chess_move = san_to_move(&board, token)
+                .ok_or_else(|| format!("unresolvable move in PGN: {}", token))?;
+
+            if !game.make_move(chess_move) {
+                return Err(format!("illegal move in PGN: {}", token).into());
+            }
+            history.push((board, chess_move));
+        }
+
+        self.game = game;
+        self.move_history = history;
+        self.selected_square = None;
+        self.possible_moves.clear();
+        self.history_cursor = None;
+        self.pondering = false;
+        self.ponder_move = None;
+        self.time_forfeit = None;
+        self.white_time_left = DEFAULT_TIME;
+        self.black_time_left = DEFAULT_TIME;
+        Ok(())
+    }
+
+    /// Applies the buffered text from the FEN/PGN-path input overlay,
+    /// closing it whether or not the load succeeds.
+    fn submit_input(&mut self) {
+        let Some(mode) = self.input_mode.take() else {
+            return;
+        };
+        let value = std::mem::take(&mut self.input_buffer);
+
+        match mode {
+            InputMode::Fen => match self.load_fen(&value) {
+                Ok(()) => self.message = "Loaded position from FEN".to_string(),
+                Err(e) => self.message = format!("Invalid FEN: {}", e),
+            },
+            InputMode::PgnPath => match self.load_pgn(&value) {
+                Ok(()) => self.message = format!("Loaded game from {}", value),
+                Err(e) => self.message = format!("Failed to load PGN: {}", e),
+            },
         }
     }
 
@@ -63,43 +535,154 @@ impl App {
             .spawn()?;
 
         self.engine_process = Some(process);
-        
-        // Initialize UCI engine
+
         if let Some(ref mut process) = self.engine_process {
             let mut stdin = process.stdin.take().unwrap();
+            let stdout = process.stdout.take().unwrap();
+            let mut reader = BufReader::new(stdout);
+            let mut line = String::new();
+
+            // Ask the engine to identify itself and read its advertised
+            // options until `uciok`, so we only ever send `setoption` for
+            // things this particular build actually supports.
             stdin.write_all(b"uci\n")?;
+            stdin.flush()?;
+            loop {
+                line.clear();
+                if reader.read_line(&mut line)? == 0 {
+                    break;
+                }
+                let trimmed = line.trim();
+                if trimmed == "uciok" {
+                    break;
+                }
+                if let Some(option) = parse_uci_option_line(trimmed) {
+                    self.engine_options.push(option);
+                }
+            }
+
+            self.engine_settings = self
+                .engine_options
+                .iter()
+                .map(|option| {
+                    DEFAULT_ENGINE_SETTINGS
+                        .iter()
+                        .find(|(name, _)| *name == option.name)
+                        .map(|(_, value)| value.to_string())
+                        .unwrap_or_else(|| option.default.clone())
+                })
+                .collect();
+
+            for (option, value) in self.engine_options.iter().zip(self.engine_settings.iter()) {
+                if option.kind == UciOptionKind::Button {
+                    continue;
+                }
+                stdin.write_all(
+                    format!("setoption name {} value {}\n", option.name, value).as_bytes(),
+                )?;
+            }
+
+            // Block until the engine confirms every option took effect and
+            // it is ready to search.
             stdin.write_all(b"isready\n")?;
-            stdin.write_all(b"setoption name Skill Level value 10\n")?; // Set skill level (1-20)
-            stdin.write_all(b"setoption name Threads value 4\n")?; // Use 4 threads
-            stdin.write_all(b"setoption name Hash value 128\n")?; // Use 128MB hash
-            stdin.write_all(b"setoption name UCI_AnalyseMode value false\n")?;
-            stdin.write_all(b"setoption name UCI_LimitStrength value false\n")?;
             stdin.flush()?;
-            
-            // Read engine output in a separate thread
-            let stdout = process.stdout.take().unwrap();
-            let reader = BufReader::new(stdout);
-            
-            // Get a clone of the sender to pass to the thread
+            loop {
+                line.clear();
+                if reader.read_line(&mut line)? == 0 {
+                    break;
+                }
+                if line.trim() == "readyok" {
+                    break;
+                }
+            }
+
+            // From here on, read engine output in a background thread.
             let tx_clone = self.engine_move_sender.as_ref().unwrap().clone();
-            
+            let analysis_clone = self.analysis.clone();
+
             thread::spawn(move || {
-                for line in reader.lines() {
-                    if let Ok(line) = line {
-                        if line.starts_with("bestmove") {
-                            let parts: Vec<&str> = line.split_whitespace().collect();
-                            if parts.len() >= 2 {
-                                tx_clone.send(parts[1].to_string()).unwrap_or(());
+                let mut reader = reader;
+                let mut line = String::new();
+                loop {
+                    line.clear();
+                    match reader.read_line(&mut line) {
+                        Ok(0) | Err(_) => break,
+                        Ok(_) => {
+                            let line = line.trim();
+                            if line.starts_with("bestmove") {
+                                let parts: Vec<&str> = line.split_whitespace().collect();
+                                if parts.len() >= 2 {
+                                    let ponder = parts
+                                        .iter()
+                                        .position(|&p| p == "ponder")
+                                        .and_then(|i| parts.get(i + 1))
+                                        .map(|s| s.to_string());
+                                    tx_clone.send((parts[1].to_string(), ponder)).unwrap_or(());
+                                }
+                            } else if let Some(info) = parse_info_line(line) {
+                                *analysis_clone.lock().unwrap() = info;
                             }
                         }
                     }
                 }
             });
-            
-            // Return stdin to the process
+
             process.stdin = Some(stdin);
         }
-        
+
+        Ok(())
+    }
+
+    /// Nudges the currently selected engine setting: toggles a `check`,
+    /// steps a `spin` by 1 (clamped to its advertised min/max), or cycles a
+    /// `combo` through its `var` list. No-op for `string`/`button` options.
+    fn adjust_selected_setting(&mut self, delta: i64) {
+        let Some(option) = self.engine_options.get(self.settings_selected) else {
+            return;
+        };
+        let Some(current) = self.engine_settings.get(self.settings_selected).cloned() else {
+            return;
+        };
+
+        let new_value = match option.kind {
+            UciOptionKind::Check => {
+                if current == "true" { "false".to_string() } else { "true".to_string() }
+            }
+            UciOptionKind::Spin => {
+                let value = current.parse::<i64>().unwrap_or(0) + delta;
+                let min = option.min.unwrap_or(i64::MIN);
+                let max = option.max.unwrap_or(i64::MAX);
+                value.clamp(min, max).to_string()
+            }
+            UciOptionKind::Combo if !option.vars.is_empty() => {
+                let pos = option.vars.iter().position(|v| v == &current).unwrap_or(0) as i64;
+                let len = option.vars.len() as i64;
+                option.vars[(pos + delta).rem_euclid(len) as usize].clone()
+            }
+            _ => current,
+        };
+
+        self.engine_settings[self.settings_selected] = new_value;
+    }
+
+    /// Sends `setoption` for every advertised option at its current value,
+    /// followed by `isready`. It does not wait for the `readyok` reply; the
+    /// background reader thread receives and ignores it.
+    fn apply_engine_settings(&mut self) -> Result<(), Box<dyn Error>> {
+        if let Some(ref mut process) = self.engine_process {
+            if let Some(stdin) = process.stdin.as_mut() {
+                for (option, value) in self.engine_options.iter().zip(self.engine_settings.iter()) {
+                    if option.kind == UciOptionKind::Button {
+                        continue;
+                    }
+                    stdin.write_all(
+                        format!("setoption name {} value {}\n", option.name, value).as_bytes(),
+                    )?;
+                }
+                stdin.write_all(b"isready\n")?;
+                stdin.flush()?;
+            }
+        }
         Ok(())
     }
 
@@ -110,38 +693,138 @@ impl App {
                 let fen = self.game.current_position().to_string();
                 let position_cmd = format!("position fen {}\n", fen);
                 stdin.write_all(position_cmd.as_bytes())?;
-                
-                // Ask engine to think
-                stdin.write_all(b"go movetime 2000\n")?;
+
+                // Let the engine manage its own time like a real clock game.
+                let go_cmd = format!(
+                    "go wtime {} btime {} winc {} binc {}\n",
+                    self.white_time_left.as_millis(),
+                    self.black_time_left.as_millis(),
+                    self.white_increment.as_millis(),
+                    self.black_increment.as_millis(),
+                );
+                stdin.write_all(go_cmd.as_bytes())?;
                 stdin.flush()?;
-                
+
                 self.thinking = true;
+                self.pondering = false;
+                self.ponder_move = None;
                 self.message = "Engine is thinking...".to_string();
             }
         }
-        
+
         Ok(())
     }
 
+    /// Tells the engine to search the position after its predicted reply
+    /// `predicted_uci`, in the background while the human decides.
+    fn start_pondering(&mut self, predicted_uci: &str) -> Result<(), Box<dyn Error>> {
+        let board = self.game.current_position();
+        let Some(predicted_move) = uci_to_move(&board, predicted_uci) else {
+            self.pondering = false;
+            self.ponder_move = None;
+            return Ok(());
+        };
+
+        if let Some(ref mut process) = self.engine_process {
+            if let Some(stdin) = process.stdin.as_mut() {
+                let position_cmd =
+                    format!("position fen {} moves {}\n", board, predicted_uci);
+                stdin.write_all(position_cmd.as_bytes())?;
+
+                // Same clock as a real `go`, so a `ponderhit` still runs out
+                // and emits a `bestmove` instead of searching forever.
+                let go_cmd = format!(
+                    "go ponder wtime {} btime {} winc {} binc {}\n",
+                    self.white_time_left.as_millis(),
+                    self.black_time_left.as_millis(),
+                    self.white_increment.as_millis(),
+                    self.black_increment.as_millis(),
+                );
+                stdin.write_all(go_cmd.as_bytes())?;
+                stdin.flush()?;
+            }
+        }
+
+        self.ponder_move = Some(predicted_move);
+        self.pondering = true;
+        Ok(())
+    }
+
+    /// Reacts to the human's move when the engine might already be
+    /// pondering: converts a ponder hit into the real search with
+    /// `ponderhit`, or stops the wrong guess and starts a fresh search.
+    fn after_player_move(&mut self, chess_move: ChessMove) -> Result<(), Box<dyn Error>> {
+        if self.pondering {
+            let is_ponder_hit = self.ponder_move == Some(chess_move);
+            self.pondering = false;
+            self.ponder_move = None;
+
+            if let Some(ref mut process) = self.engine_process {
+                if let Some(stdin) = process.stdin.as_mut() {
+                    stdin.write_all(if is_ponder_hit { b"ponderhit\n" } else { b"stop\n" })?;
+                    stdin.flush()?;
+                }
+            }
+
+            if !is_ponder_hit {
+                // `stop` still makes the engine emit a bestmove for the
+                // ponder search it just abandoned; it reflects the
+                // predicted position, not the one we're about to search.
+                self.discard_next_bestmove = true;
+            }
+
+            if is_ponder_hit {
+                self.thinking = true;
+                self.message = "Engine is thinking (ponder hit)...".to_string();
+                return Ok(());
+            }
+        }
+
+        self.get_engine_move()
+    }
+
     fn select_square(&mut self, square: Square) {
+        if self.time_forfeit.is_some() {
+            return;
+        }
+
+        let board = self.display_board();
+
         if let Some(_selected) = self.selected_square {
             // If a square is already selected, try to make a move
             let possible_move = self.possible_moves.iter().find(|m| m.get_dest() == square);
-            
+
             if let Some(chess_move) = possible_move {
-                if self.game.make_move(*chess_move) {
+                let chess_move = *chess_move;
+
+                // Moving while browsing history resumes play from the
+                // displayed position, truncating whatever came after it.
+                if let Some(idx) = self.history_cursor {
+                    self.game = Game::new_with_board(board);
+                    self.move_history.truncate(idx);
+                    self.history_cursor = None;
+                }
+
+                let board_before = self.game.current_position();
+                if self.game.make_move(chess_move) {
+                    self.move_history.push((board_before, chess_move));
+                    match !self.game.side_to_move() {
+                        Color::White => self.white_time_left += self.white_increment,
+                        Color::Black => self.black_time_left += self.black_increment,
+                    }
                     self.message = format!("Move: {}", chess_move);
                     self.selected_square = None;
                     self.possible_moves.clear();
-                    
+
                     // Check game status
                     match self.game.result() {
                         Some(result) => {
                             self.message = format!("Game over: {:?}", result);
                         }
                         None => {
-                            // Get engine move
-                            if let Err(e) = self.get_engine_move() {
+                            // Get engine move, converting a ponder hit into
+                            // the real search where possible.
+                            if let Err(e) = self.after_player_move(chess_move) {
                                 self.message = format!("Engine error: {}", e);
                             }
                         }
@@ -154,9 +837,8 @@ impl App {
             }
         } else {
             // Select the square if it has a piece of the current player's color
-            let board = self.game.current_position();
             if let Some(_piece) = board.piece_on(square) {
-                if board.color_on(square) == Some(self.game.side_to_move()) {
+                if board.color_on(square) == Some(board.side_to_move()) {
                     self.selected_square = Some(square);
                     self.update_possible_moves();
                 }
@@ -166,11 +848,11 @@ impl App {
 
     fn update_possible_moves(&mut self) {
         self.possible_moves.clear();
-        
+
         if let Some(square) = self.selected_square {
-            let board = self.game.current_position();
+            let board = self.display_board();
             let move_gen = MoveGen::new_legal(&board);
-            
+
             for chess_move in move_gen {
                 if chess_move.get_source() == square {
                     self.possible_moves.push(chess_move);
@@ -192,50 +874,31 @@ fn run_app(
 
         // Check for engine moves
         if let Some(ref rx) = app.engine_move_receiver {
-            if let Ok(best_move) = rx.try_recv() {
-                // Try to parse UCI format (e.g., "d2d4")
-                if best_move.len() >= 4 {
-                    let from_file = (best_move.chars().nth(0).unwrap() as u8 - b'a') as usize;
-                    let from_rank = (best_move.chars().nth(1).unwrap() as u8 - b'1') as usize;
-                    let to_file = (best_move.chars().nth(2).unwrap() as u8 - b'a') as usize;
-                    let to_rank = (best_move.chars().nth(3).unwrap() as u8 - b'1') as usize;
-                    
-                    if from_file < 8 && from_rank < 8 && to_file < 8 && to_rank < 8 {
-                        let from_square = Square::make_square(
-                            Rank::from_index(from_rank),
-                            File::from_index(from_file)
-                        );
-                        let to_square = Square::make_square(
-                            Rank::from_index(to_rank),
-                            File::from_index(to_file)
-                        );
-                        
-                        // Find the move in legal moves
-                        let board = app.game.current_position();
-                        let move_gen = MoveGen::new_legal(&board);
-                        
-                        for m in move_gen {
-                            if m.get_source() == from_square && m.get_dest() == to_square {
-                                // Handle promotion if needed
-                                let promotion = if best_move.len() >= 5 {
-                                    match best_move.chars().nth(4).unwrap() {
-                                        'q' => Some(Piece::Queen),
-                                        'r' => Some(Piece::Rook),
-                                        'b' => Some(Piece::Bishop),
-                                        'n' => Some(Piece::Knight),
-                                        _ => None
-                                    }
-                                } else {
-                                    None
-                                };
-                                
-                                if promotion.is_none() || m.get_promotion() == promotion {
-                                    if app.game.make_move(m) {
-                                        app.message = format!("Engine moved: {}", best_move);
-                                        app.thinking = false;
-                                        break;
-                                    }
+            if let Ok((best_move, ponder_move)) = rx.try_recv() {
+                if app.discard_next_bestmove {
+                    // The stale bestmove from a `stop` after a ponder miss;
+                    // it was searched for the predicted position, not the
+                    // one actually on the board.
+                    app.discard_next_bestmove = false;
+                } else {
+                    let board = app.game.current_position();
+                    if let Some(m) = uci_to_move(&board, &best_move) {
+                        if app.game.make_move(m) {
+                            app.move_history.push((board, m));
+                            match !app.game.side_to_move() {
+                                Color::White => app.white_time_left += app.white_increment,
+                                Color::Black => app.black_time_left += app.black_increment,
+                            }
+                            app.message = format!("Engine moved: {}", best_move);
+                            app.thinking = false;
+
+                            if let Some(ref p) = ponder_move {
+                                if let Err(e) = app.start_pondering(p) {
+                                    app.message = format!("Ponder error: {}", e);
                                 }
+                            } else {
+                                app.pondering = false;
+                                app.ponder_move = None;
                             }
                         }
                     }
@@ -249,29 +912,92 @@ fn run_app(
 
         if crossterm::event::poll(timeout)? {
             if let Event::Key(key) = event::read()? {
-                match key.code {
-                    KeyCode::Char('q') => return Ok(()),
-                    KeyCode::Char(c) if c >= 'a' && c <= 'h' => {
-                        // File selection
-                        let file = File::from_index((c as u8 - b'a') as usize);
-                        
-                        // Wait for rank selection
-                        if let Event::Key(key) = event::read()? {
-                            if let KeyCode::Char(r) = key.code {
-                                if r >= '1' && r <= '8' {
-                                    let rank = Rank::from_index((r as u8 - b'1') as usize);
-                                    let square = Square::make_square(rank, file);
-                                    app.select_square(square);
+                if app.input_mode.is_some() {
+                    match key.code {
+                        KeyCode::Esc => {
+                            app.input_mode = None;
+                            app.input_buffer.clear();
+                        }
+                        KeyCode::Enter => app.submit_input(),
+                        KeyCode::Backspace => {
+                            app.input_buffer.pop();
+                        }
+                        KeyCode::Char(c) => app.input_buffer.push(c),
+                        _ => {}
+                    }
+                } else if app.settings_open {
+                    match key.code {
+                        KeyCode::Esc => app.settings_open = false,
+                        KeyCode::Up => {
+                            app.settings_selected = app.settings_selected.saturating_sub(1);
+                        }
+                        KeyCode::Down => {
+                            if app.settings_selected + 1 < app.engine_options.len() {
+                                app.settings_selected += 1;
+                            }
+                        }
+                        KeyCode::Left => app.adjust_selected_setting(-1),
+                        KeyCode::Right => app.adjust_selected_setting(1),
+                        KeyCode::Enter => {
+                            match app.apply_engine_settings() {
+                                Ok(()) => app.message = "Engine settings applied".to_string(),
+                                Err(e) => app.message = format!("Failed to apply settings: {}", e),
+                            }
+                            app.settings_open = false;
+                        }
+                        _ => {}
+                    }
+                } else {
+                    match key.code {
+                        KeyCode::Char('q') => return Ok(()),
+                        KeyCode::Char('s') => {
+                            let path = "game.pgn";
+                            match fs::write(path, app.to_pgn()) {
+                                Ok(()) => app.message = format!("Saved game to {}", path),
+                                Err(e) => app.message = format!("Failed to save game: {}", e),
+                            }
+                        }
+                        KeyCode::Char('o') => {
+                            if app.engine_options.is_empty() {
+                                app.message = "Engine advertised no configurable options".to_string();
+                            } else {
+                                app.settings_open = true;
+                                app.settings_selected = 0;
+                            }
+                        }
+                        KeyCode::Char('p') => {
+                            app.input_mode = Some(InputMode::Fen);
+                            app.input_buffer.clear();
+                        }
+                        KeyCode::Char('i') => {
+                            app.input_mode = Some(InputMode::PgnPath);
+                            app.input_buffer = "game.pgn".to_string();
+                        }
+                        KeyCode::Left => app.browse_back(),
+                        KeyCode::Right => app.browse_forward(),
+                        KeyCode::Char(c) if c >= 'a' && c <= 'h' => {
+                            // File selection
+                            let file = File::from_index((c as u8 - b'a') as usize);
+
+                            // Wait for rank selection
+                            if let Event::Key(key) = event::read()? {
+                                if let KeyCode::Char(r) = key.code {
+                                    if r >= '1' && r <= '8' {
+                                        let rank = Rank::from_index((r as u8 - b'1') as usize);
+                                        let square = Square::make_square(rank, file);
+                                        app.select_square(square);
+                                    }
                                 }
                             }
                         }
+                        _ => {}
                     }
-                    _ => {}
                 }
             }
         }
 
         if last_tick.elapsed() >= tick_rate {
+            app.tick_clock(last_tick.elapsed());
             last_tick = Instant::now();
         }
     }
@@ -284,18 +1010,33 @@ fn ui(f: &mut ratatui::Frame, app: &App) {
         .constraints([
             Constraint::Length(1),  // Status line
             Constraint::Min(10),    // Chess board
+            Constraint::Length(4),  // Analysis panel (eval bar + PV)
             Constraint::Length(3),  // Message area
         ])
         .split(f.size());
 
     // Status line
-    let status = format!("Turn: {}", if app.game.side_to_move() == Color::White { "White" } else { "Black" });
+    let status = if let Some(idx) = app.history_cursor {
+        format!(
+            "Viewing move {}/{}  (Right arrow to return to the live position)",
+            idx,
+            app.move_history.len()
+        )
+    } else {
+        format!(
+            "Turn: {}  |  White: {}  Black: {}{}",
+            if app.game.side_to_move() == Color::White { "White" } else { "Black" },
+            format_clock(app.time_left(Color::White)),
+            format_clock(app.time_left(Color::Black)),
+            if app.pondering { "  |  Engine pondering" } else { "" },
+        )
+    };
     let status_widget = Paragraph::new(status)
         .style(Style::default());
     f.render_widget(status_widget, chunks[0]);
 
     // Chess board
-    let board = app.game.current_position();
+    let board = app.display_board();
     let mut board_text = Vec::new();
     
     // Add column labels
@@ -379,10 +1120,92 @@ fn ui(f: &mut ratatui::Frame, app: &App) {
         .block(Block::default().borders(Borders::ALL).title("Chess"));
     f.render_widget(board_widget, chunks[1]);
 
+    // Analysis panel: eval bar + principal variation translated into SAN
+    let analysis = app.analysis.lock().unwrap().clone();
+    let pv = if analysis.pv.is_empty() {
+        "PV: --".to_string()
+    } else {
+        format!("PV: {}", pv_to_san(&board, &analysis.pv))
+    };
+    let nps = analysis
+        .nps
+        .map(|n| n.to_string())
+        .unwrap_or_else(|| "--".to_string());
+    let analysis_text = Text::from(vec![
+        ratatui::text::Line::from(format!(
+            "Eval: {} {}  Depth: {}  Nodes/s: {}",
+            eval_bar(&analysis, board.side_to_move()),
+            eval_label(&analysis, board.side_to_move()),
+            analysis.depth,
+            nps,
+        )),
+        ratatui::text::Line::from(pv),
+    ]);
+    let analysis_widget = Paragraph::new(analysis_text)
+        .block(Block::default().borders(Borders::ALL).title("Analysis"));
+    f.render_widget(analysis_widget, chunks[2]);
+
     // Message area
     let message_widget = Paragraph::new(app.message.clone())
         .block(Block::default().borders(Borders::ALL).title("Messages"));
-    f.render_widget(message_widget, chunks[2]);
+    f.render_widget(message_widget, chunks[3]);
+
+    // Engine settings overlay, shown on top of everything else while open.
+    if app.settings_open {
+        let area = centered_rect(60, 50, f.size());
+        f.render_widget(ratatui::widgets::Clear, area);
+
+        let lines: Vec<ratatui::text::Line> = app
+            .engine_options
+            .iter()
+            .zip(app.engine_settings.iter())
+            .enumerate()
+            .map(|(i, (option, value))| {
+                let marker = if i == app.settings_selected { ">" } else { " " };
+                ratatui::text::Line::from(format!("{} {}: {}", marker, option.name, value))
+            })
+            .collect();
+        let settings_widget = Paragraph::new(Text::from(lines)).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Engine Settings (Up/Down select, Left/Right adjust, Enter apply, Esc close)"),
+        );
+        f.render_widget(settings_widget, area);
+    }
+
+    // FEN/PGN-path input overlay.
+    if let Some(mode) = &app.input_mode {
+        let title = match mode {
+            InputMode::Fen => "Load position from FEN (Enter to apply, Esc to cancel)",
+            InputMode::PgnPath => "Load PGN file (Enter to apply, Esc to cancel)",
+        };
+        let area = centered_rect(60, 20, f.size());
+        f.render_widget(ratatui::widgets::Clear, area);
+        let input_widget = Paragraph::new(app.input_buffer.clone())
+            .block(Block::default().borders(Borders::ALL).title(title));
+        f.render_widget(input_widget, area);
+    }
+}
+
+/// Carves a centered rectangle of `percent_x`/`percent_y` out of `area`.
+fn centered_rect(percent_x: u16, percent_y: u16, area: ratatui::layout::Rect) -> ratatui::layout::Rect {
+    let popup_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage((100 - percent_y) / 2),
+            Constraint::Percentage(percent_y),
+            Constraint::Percentage((100 - percent_y) / 2),
+        ])
+        .split(area);
+
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ])
+        .split(popup_layout[1])[1]
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
@@ -1,4 +1,5 @@
 use chess::{Color, Piece, Square, Rank, File};
+use crossterm::event::KeyCode;
 use ratatui::{
     backend::Backend,
     layout::{Constraint, Direction, Layout},
@@ -8,15 +9,69 @@ use ratatui::{
     Frame,
 };
 
+use crate::engine::AnalysisInfo;
 use crate::game::ChessGame;
 
-pub fn draw_ui<B: Backend>(f: &mut Frame, game: &ChessGame) {
+/// Applies undo/redo keybinds (`u` / `r`) to the game. Returns `true` if the
+/// key was handled so the caller knows not to treat it as a board input.
+pub fn handle_history_key(key: KeyCode, game: &mut ChessGame) -> bool {
+    match key {
+        KeyCode::Char('u') => {
+            game.undo_move();
+            true
+        }
+        KeyCode::Char('r') => {
+            game.redo_move();
+            true
+        }
+        _ => false,
+    }
+}
+
+const EVAL_BAR_WIDTH: i32 = 20;
+
+/// Renders the eval bar as a fixed-width ASCII gauge, e.g. `[############--------]`.
+/// Mate scores peg the bar fully to whichever side is winning. UCI scores are
+/// relative to the side to move, so `perspective` flips them to White's view.
+fn eval_bar(analysis: &AnalysisInfo, perspective: Color) -> String {
+    let sign = if perspective == Color::Black { -1 } else { 1 };
+
+    let filled = if let Some(mate) = analysis.score_mate {
+        if mate * sign > 0 { EVAL_BAR_WIDTH } else { 0 }
+    } else {
+        let cp = (analysis.score_cp.unwrap_or(0) * sign).clamp(-1000, 1000);
+        (cp + 1000) * EVAL_BAR_WIDTH / 2000
+    }
+    .clamp(0, EVAL_BAR_WIDTH) as usize;
+
+    format!(
+        "[{}{}]",
+        "#".repeat(filled),
+        "-".repeat(EVAL_BAR_WIDTH as usize - filled)
+    )
+}
+
+/// Same White-relative normalization as `eval_bar`; see its doc comment.
+fn eval_label(analysis: &AnalysisInfo, perspective: Color) -> String {
+    let sign = if perspective == Color::Black { -1 } else { 1 };
+
+    if let Some(mate) = analysis.score_mate {
+        format!("#{}", mate * sign)
+    } else if let Some(cp) = analysis.score_cp {
+        format!("{:+.2}", (cp * sign) as f32 / 100.0)
+    } else {
+        "--".to_string()
+    }
+}
+
+pub fn draw_ui<B: Backend>(f: &mut Frame, game: &ChessGame, analysis: &AnalysisInfo) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .margin(1)
         .constraints([
             Constraint::Length(1),  // Status line
             Constraint::Min(10),    // Chess board
+            Constraint::Length(4),  // Analysis panel (eval bar + PV)
             Constraint::Length(3),  // Message area
         ])
         .split(f.size());
@@ -100,8 +155,27 @@ pub fn draw_ui<B: Backend>(f: &mut Frame, game: &ChessGame) {
         .block(Block::default().borders(Borders::ALL).title("Chess"));
     f.render_widget(board_widget, chunks[1]);
 
+    // Analysis panel: eval bar + principal variation
+    let pv = if analysis.pv.is_empty() {
+        "PV: --".to_string()
+    } else {
+        format!("PV: {}", game.pv_to_san(&analysis.pv))
+    };
+    let analysis_text = Text::from(vec![
+        Line::from(format!(
+            "Eval: {} {}  Depth: {}",
+            eval_bar(analysis, game.side_to_move()),
+            eval_label(analysis, game.side_to_move()),
+            analysis.depth
+        )),
+        Line::from(pv),
+    ]);
+    let analysis_widget = Paragraph::new(analysis_text)
+        .block(Block::default().borders(Borders::ALL).title("Analysis"));
+    f.render_widget(analysis_widget, chunks[2]);
+
     // Message area
     let message_widget = Paragraph::new(game.message())
         .block(Block::default().borders(Borders::ALL).title("Messages"));
-    f.render_widget(message_widget, chunks[2]);
+    f.render_widget(message_widget, chunks[3]);
 }